@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::diagnostics::{CompileError, Span};
+use crate::{find_interpolations, Assignment, Expr, FunctionCall, Statement, ValueType, VariableDeclaration, Ast};
+
+/// Functions the generated JS can call without a matching `fn` in the
+/// program, because the backends provide them directly.
+const BUILTIN_FUNCTIONS: &[&str] = &["print"];
+
+struct Symbol {
+    mutable: bool,
+    ty: &'static str,
+}
+
+/// A stack of lexical scopes: one `HashMap` per enclosing block, innermost
+/// last. Reads search outward through the whole stack (a block can see its
+/// enclosing scopes), but a declaration only ever inserts into - and is
+/// checked for reassignment against - the innermost scope, so sibling blocks
+/// (two `if` bodies, say) don't collide with each other.
+struct Scopes {
+    stack: Vec<HashMap<String, Symbol>>,
+}
+
+impl Scopes {
+    fn new() -> Self {
+        Scopes { stack: vec![HashMap::new()] }
+    }
+
+    fn push(&mut self) {
+        self.stack.push(HashMap::new());
+    }
+
+    fn pop(&mut self) {
+        self.stack.pop();
+    }
+
+    fn declare(&mut self, name: String, symbol: Symbol) {
+        self.stack.last_mut().expect("at least one scope").insert(name, symbol);
+    }
+
+    /// Looks up `name` in the innermost scope only, for deciding whether a
+    /// `var` is a fresh declaration or a same-scope redeclaration.
+    fn get_in_current_scope(&self, name: &str) -> Option<&Symbol> {
+        self.stack.last().expect("at least one scope").get(name)
+    }
+
+    /// Looks up `name` through every enclosing scope, innermost first, for
+    /// resolving a read/assignment reference.
+    fn lookup(&self, name: &str) -> Option<&Symbol> {
+        self.stack.iter().rev().find_map(|scope| scope.get(name))
+    }
+}
+
+/// Read-only context threaded through every `check_*` call: the set of
+/// callable function names, and the original source text, needed to recover
+/// a string literal's raw (pre-escape-decoding) contents for span math.
+struct AnalysisContext<'a> {
+    source: &'a str,
+    functions: HashSet<String>,
+}
+
+/// Walks `ast` building a symbol table, rejecting references to undeclared
+/// variables, reassignments of `const` (non-`mut`) variables, and
+/// declarations that change a variable's type. Mirrors `parser`'s fail-fast
+/// style: the first problem found is returned instead of generating JS for
+/// a broken program.
+pub fn analyze(ast: &Ast, source: &str) -> Result<(), CompileError> {
+    let mut scopes = Scopes::new();
+    let mut functions: HashSet<String> = BUILTIN_FUNCTIONS.iter().map(|name| name.to_string()).collect();
+    collect_function_names(ast, &mut functions);
+    let ctx = AnalysisContext { source, functions };
+    check_block(ast, &mut scopes, &ctx)
+}
+
+/// Gathers every `fn` name declared anywhere in `ast`, including nested
+/// blocks, before any call is checked - matching how JS hoists function
+/// declarations, so a function may be called above its own definition.
+fn collect_function_names(ast: &Ast, functions: &mut HashSet<String>) {
+    for statement in ast {
+        match statement {
+            Statement::FunctionDef(function) => {
+                functions.insert(function.name.clone());
+                collect_function_names(&function.body, functions);
+            },
+            Statement::If(statement) => {
+                collect_function_names(&statement.then_block, functions);
+                if let Some(else_block) = &statement.else_block {
+                    collect_function_names(else_block, functions);
+                }
+            },
+            Statement::While(statement) => collect_function_names(&statement.body, functions),
+            _ => {},
+        }
+    }
+}
+
+fn check_block(block: &Ast, scopes: &mut Scopes, ctx: &AnalysisContext) -> Result<(), CompileError> {
+    for statement in block {
+        check_statement(statement, scopes, ctx)?;
+    }
+    Ok(())
+}
+
+/// Checks `block` in a fresh child scope that is discarded afterward, so
+/// names declared inside don't leak into the enclosing scope.
+fn check_scoped_block(block: &Ast, scopes: &mut Scopes, ctx: &AnalysisContext) -> Result<(), CompileError> {
+    scopes.push();
+    let result = check_block(block, scopes, ctx);
+    scopes.pop();
+    result
+}
+
+fn check_statement(statement: &Statement, scopes: &mut Scopes, ctx: &AnalysisContext) -> Result<(), CompileError> {
+    match statement {
+        Statement::VariableDeclaration(declaration) => check_declaration(declaration, scopes, ctx),
+        Statement::Assignment(assignment) => check_assignment(assignment, scopes, ctx),
+        Statement::FunctionCall(call) => check_call(call, scopes, ctx),
+        Statement::If(statement) => {
+            check_expr(&statement.cond, scopes, ctx)?;
+            check_scoped_block(&statement.then_block, scopes, ctx)?;
+            if let Some(else_block) = &statement.else_block {
+                check_scoped_block(else_block, scopes, ctx)?;
+            }
+            Ok(())
+        },
+        Statement::While(statement) => {
+            check_expr(&statement.cond, scopes, ctx)?;
+            check_scoped_block(&statement.body, scopes, ctx)
+        },
+        Statement::FunctionDef(function) => {
+            scopes.push();
+            for param in &function.params {
+                scopes.declare(param.clone(), Symbol { mutable: true, ty: "unknown" });
+            }
+            let result = check_block(&function.body, scopes, ctx);
+            scopes.pop();
+            result
+        },
+        Statement::Return(value) => match value {
+            Some(expr) => check_expr(expr, scopes, ctx),
+            None => Ok(()),
+        },
+    }
+}
+
+fn check_call(call: &FunctionCall, scopes: &Scopes, ctx: &AnalysisContext) -> Result<(), CompileError> {
+    if !ctx.functions.contains(&call.name) {
+        return Err(CompileError::new(format!("Undeclared function `{}`!", call.name), call.span));
+    }
+    for argument in &call.arguments {
+        check_expr(argument, scopes, ctx)?;
+    }
+    Ok(())
+}
+
+fn check_declaration(
+    declaration: &VariableDeclaration,
+    scopes: &mut Scopes,
+    ctx: &AnalysisContext,
+) -> Result<(), CompileError> {
+    check_expr(&declaration.value, scopes, ctx)?;
+    let ty = type_of(&declaration.value, scopes);
+
+    if let Some(existing) = scopes.get_in_current_scope(&declaration.name) {
+        if !existing.mutable {
+            return Err(CompileError::new(
+                format!("Cannot reassign const variable `{}`!", declaration.name),
+                declaration.span,
+            ));
+        }
+        if existing.ty != ty {
+            return Err(CompileError::new(
+                format!(
+                    "Type mismatch: `{}` was declared as {} but is assigned a {}!",
+                    declaration.name, existing.ty, ty
+                ),
+                declaration.span,
+            ));
+        }
+    }
+
+    scopes.declare(
+        declaration.name.clone(),
+        Symbol {
+            mutable: declaration.mutable,
+            ty,
+        },
+    );
+    Ok(())
+}
+
+/// Checks an assignment to an already-declared variable: the target must
+/// exist and be `mut`, unlike a `var` declaration which may introduce a new
+/// name.
+fn check_assignment(assignment: &Assignment, scopes: &Scopes, ctx: &AnalysisContext) -> Result<(), CompileError> {
+    check_expr(&assignment.value, scopes, ctx)?;
+
+    match scopes.lookup(&assignment.name) {
+        Some(symbol) if symbol.mutable => Ok(()),
+        Some(_) => Err(CompileError::new(
+            format!("Cannot reassign const variable `{}`!", assignment.name),
+            assignment.span,
+        )),
+        None => Err(CompileError::new(
+            format!("Undeclared variable `{}`!", assignment.name),
+            assignment.span,
+        )),
+    }
+}
+
+/// Resolves the static type of `expr`; identifiers resolve through `scopes`
+/// since `check_expr` has already confirmed they are declared.
+fn type_of(expr: &Expr, scopes: &Scopes) -> &'static str {
+    match expr {
+        Expr::Literal(ValueType::Str(_), _) => "string",
+        Expr::Literal(ValueType::Int(_), _) => "int",
+        Expr::Ident(name, _) => scopes.lookup(name).map(|symbol| symbol.ty).unwrap_or("unknown"),
+        Expr::Binary { .. } => "int",
+        Expr::Call(_) => "unknown",
+    }
+}
+
+fn check_expr(expr: &Expr, scopes: &Scopes, ctx: &AnalysisContext) -> Result<(), CompileError> {
+    match expr {
+        Expr::Literal(ValueType::Str(_), span) => check_string_interpolation(*span, scopes, ctx),
+        Expr::Literal(ValueType::Int(_), _) => Ok(()),
+        Expr::Ident(name, span) => {
+            if scopes.lookup(name).is_some() {
+                Ok(())
+            } else {
+                Err(CompileError::new(format!("Undeclared variable `{}`!", name), *span))
+            }
+        },
+        Expr::Binary { left, right, .. } => {
+            check_expr(left, scopes, ctx)?;
+            check_expr(right, scopes, ctx)
+        },
+        Expr::Call(call) => check_call(call, scopes, ctx),
+    }
+}
+
+/// Checks `{name}` segments embedded in string literals, the same way
+/// `generate_js` detects them to emit template-literal interpolation.
+/// `literal_span` is the `Expr::Literal`'s own span over the RAW source
+/// (quotes included, escapes not yet decoded) - so interpolations are
+/// located by re-scanning that raw slice of `ctx.source` directly, rather
+/// than the literal's decoded `String` value, whose byte offsets shift
+/// relative to the source whenever a multi-byte escape like `\n` collapses
+/// to a single decoded byte.
+fn check_string_interpolation(literal_span: Span, scopes: &Scopes, ctx: &AnalysisContext) -> Result<(), CompileError> {
+    // start+1/end-1 skip the literal's surrounding quotes.
+    let raw = &ctx.source[literal_span.start + 1..literal_span.end - 1];
+
+    for (name, range) in find_interpolations(raw) {
+        if scopes.lookup(&name).is_none() {
+            let start = literal_span.start + 1 + range.start;
+            let end = literal_span.start + 1 + range.end;
+            return Err(CompileError::new(
+                format!("Undeclared variable `{}`!", name),
+                Span::new(start, end),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(source: &str) -> Result<(), CompileError> {
+        let tokens = crate::lexer(source).expect("lexer should succeed");
+        let ast = crate::parser(&tokens).expect("parser should succeed");
+        analyze(&ast, source)
+    }
+
+    #[test]
+    fn rejects_calls_to_undeclared_functions() {
+        assert!(check("print(totallyUndefinedFunction(1));").is_err());
+    }
+
+    #[test]
+    fn allows_calling_a_function_defined_later_in_the_program() {
+        assert!(check("print(add(1, 2)); fn add(a, b) { return a + b; }").is_ok());
+    }
+
+    #[test]
+    fn function_params_do_not_leak_into_the_enclosing_scope() {
+        assert!(check("fn add(x) { return x; } print(x);").is_err());
+    }
+
+    #[test]
+    fn sibling_blocks_may_each_declare_their_own_differently_typed_variable() {
+        let source = r#"
+            var flag = 1;
+            if flag { var y = 1; } else { var y = "text"; }
+        "#;
+        assert!(check(source).is_ok());
+    }
+
+    #[test]
+    fn mut_variables_may_be_reassigned_but_const_ones_may_not() {
+        assert!(check("var mut i = 0; i = 1;").is_ok());
+        assert!(check("var i = 0; i = 1;").is_err());
+    }
+
+    #[test]
+    fn interpolation_error_points_at_the_raw_column_after_a_preceding_escape() {
+        let source = r#"print("a\nb{undefined}");"#;
+        let error = check(source).expect_err("undefined should be rejected");
+        // Column 12 (0-indexed) is where `undefined` starts in the raw
+        // source - one past where it would be if measured against the
+        // decoded string, where `\n` has already collapsed to one byte.
+        assert_eq!(error.span, Span::new(12, 21));
+    }
+}
@@ -0,0 +1,20 @@
+use std::fs;
+use std::process::Command;
+
+use super::Backend;
+use crate::{generate_js, Ast};
+
+/// The original backend: lowers the `Ast` to JS text and runs it with
+/// `node`. This is `--target js`, the default.
+pub struct JsBackend;
+
+impl Backend for JsBackend {
+    fn compile_and_run(&self, ast: &Ast) -> std::io::Result<()> {
+        let output_filename = "index.js";
+        let js_code = generate_js(ast);
+        fs::write(output_filename, js_code)?;
+
+        Command::new("node").arg(output_filename).status()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+pub mod js;
+pub mod native;
+
+use crate::Ast;
+
+/// A compilation target: takes the parsed, analyzed `Ast` and produces a
+/// runnable artifact (a JS file run under `node`, or a standalone native
+/// executable).
+pub trait Backend {
+    fn compile_and_run(&self, ast: &Ast) -> std::io::Result<()>;
+}
+
+/// Selects a backend by `--target` value, defaulting to the JS backend for
+/// anything other than `"native"`.
+pub fn select(target: &str) -> Box<dyn Backend> {
+    match target {
+        "native" => Box::new(native::NativeBackend),
+        _ => Box::new(js::JsBackend),
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::io::{self, Error, ErrorKind};
+use std::process::Command;
+
+use cranelift_codegen::ir::{types, AbiParam, InstBuilder, Value};
+use cranelift_codegen::settings::{self, Configurable};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
+use cranelift_module::{DataDescription, DataId, FuncId, Linkage, Module};
+use cranelift_object::{ObjectBuilder, ObjectModule};
+
+use super::Backend;
+use crate::{BinaryOp, Expr, Statement, ValueType, Ast};
+
+const OBJECT_PATH: &str = "index.o";
+const EXECUTABLE_PATH: &str = "index";
+
+/// Lowers the `Ast` straight to a native executable via Cranelift instead
+/// of emitting JS, selected with `--target native`.
+///
+/// This is an initial version: it only lowers `Int` variable declarations,
+/// arithmetic, and `print` of an int or string literal. Control flow
+/// (`if`/`while`/`fn`) isn't lowered yet and is reported as an error rather
+/// than silently dropped.
+pub struct NativeBackend;
+
+impl Backend for NativeBackend {
+    fn compile_and_run(&self, ast: &Ast) -> io::Result<()> {
+        let object_bytes = compile_to_object(ast)?;
+        std::fs::write(OBJECT_PATH, object_bytes)?;
+        link_executable()?;
+
+        Command::new(format!("./{}", EXECUTABLE_PATH)).status()?;
+        Ok(())
+    }
+}
+
+fn compile_to_object(ast: &Ast) -> io::Result<Vec<u8>> {
+    let mut flag_builder = settings::builder();
+    flag_builder
+        .set("is_pic", "true")
+        .map_err(|err| codegen_error(err.to_string()))?;
+    let isa_builder = cranelift_native::builder().map_err(codegen_error)?;
+    let isa = isa_builder
+        .finish(settings::Flags::new(flag_builder))
+        .map_err(|err| codegen_error(err.to_string()))?;
+
+    let object_builder =
+        ObjectBuilder::new(isa, "mc", cranelift_module::default_libcall_names())
+            .map_err(|err| codegen_error(err.to_string()))?;
+    let mut module = ObjectModule::new(object_builder);
+
+    let print_int = declare_runtime_fn(&mut module, "mc_print_int", &[types::I64], &[])?;
+    let print_str = declare_runtime_fn(&mut module, "mc_print_str", &[types::I64], &[])?;
+
+    let mut ctx = module.make_context();
+    ctx.func.signature.returns.push(AbiParam::new(types::I32));
+
+    let mut builder_ctx = FunctionBuilderContext::new();
+    let mut builder = FunctionBuilder::new(&mut ctx.func, &mut builder_ctx);
+
+    let entry = builder.create_block();
+    builder.append_block_params_for_function_params(entry);
+    builder.switch_to_block(entry);
+    builder.seal_block(entry);
+
+    let mut lowering = Lowering {
+        module: &mut module,
+        builder,
+        vars: HashMap::new(),
+        next_var: 0,
+        next_string_id: 0,
+        print_int,
+        print_str,
+    };
+    for statement in ast {
+        lowering.lower_statement(statement)?;
+    }
+
+    let zero = lowering.builder.ins().iconst(types::I32, 0);
+    lowering.builder.ins().return_(&[zero]);
+    lowering.builder.finalize();
+
+    let main_id = module
+        .declare_function("main", Linkage::Export, &ctx.func.signature)
+        .map_err(|err| codegen_error(err.to_string()))?;
+    module
+        .define_function(main_id, &mut ctx)
+        .map_err(|err| codegen_error(err.to_string()))?;
+    module.clear_context(&mut ctx);
+
+    let product = module.finish();
+    product
+        .emit()
+        .map_err(|err| codegen_error(err.to_string()))
+}
+
+fn declare_runtime_fn(
+    module: &mut ObjectModule,
+    name: &str,
+    params: &[types::Type],
+    returns: &[types::Type],
+) -> io::Result<FuncId> {
+    let mut signature = module.make_signature();
+    for param in params {
+        signature.params.push(AbiParam::new(*param));
+    }
+    for ret in returns {
+        signature.returns.push(AbiParam::new(*ret));
+    }
+    module
+        .declare_function(name, Linkage::Import, &signature)
+        .map_err(|err| codegen_error(err.to_string()))
+}
+
+/// Per-function lowering state: the in-progress `FunctionBuilder`, the
+/// variable slots assigned to declared names, and the imported print
+/// symbols to call into.
+struct Lowering<'a> {
+    module: &'a mut ObjectModule,
+    builder: FunctionBuilder<'a>,
+    vars: HashMap<String, Variable>,
+    next_var: usize,
+    next_string_id: usize,
+    print_int: FuncId,
+    print_str: FuncId,
+}
+
+impl<'a> Lowering<'a> {
+    fn lower_statement(&mut self, statement: &Statement) -> io::Result<()> {
+        match statement {
+            Statement::VariableDeclaration(declaration) => {
+                let value = self.lower_expr(&declaration.value)?;
+                let var = self.variable_for(&declaration.name);
+                self.builder.def_var(var, value);
+                Ok(())
+            },
+            Statement::Assignment(assignment) => {
+                let value = self.lower_expr(&assignment.value)?;
+                let var = self.variable_for(&assignment.name);
+                self.builder.def_var(var, value);
+                Ok(())
+            },
+            Statement::FunctionCall(call) if call.name == "print" => {
+                for argument in &call.arguments {
+                    self.lower_print(argument)?;
+                }
+                Ok(())
+            },
+            _ => Err(unsupported("control flow and function calls are not lowered by the native backend yet")),
+        }
+    }
+
+    fn lower_print(&mut self, expr: &Expr) -> io::Result<()> {
+        match expr {
+            Expr::Literal(ValueType::Str(s), _) => {
+                let data_id = self.define_string_data(s)?;
+                let local = self.module.declare_data_in_func(data_id, self.builder.func);
+                let ptr = self.builder.ins().symbol_value(types::I64, local);
+                let callee = self.module.declare_func_in_func(self.print_str, self.builder.func);
+                self.builder.ins().call(callee, &[ptr]);
+                Ok(())
+            },
+            other => {
+                let value = self.lower_expr(other)?;
+                let callee = self.module.declare_func_in_func(self.print_int, self.builder.func);
+                self.builder.ins().call(callee, &[value]);
+                Ok(())
+            },
+        }
+    }
+
+    fn lower_expr(&mut self, expr: &Expr) -> io::Result<Value> {
+        match expr {
+            Expr::Literal(ValueType::Int(i), _) => Ok(self.builder.ins().iconst(types::I64, *i as i64)),
+            Expr::Literal(ValueType::Str(_), _) => {
+                Err(unsupported("strings are only supported as a direct print argument"))
+            },
+            Expr::Ident(name, _) => {
+                let var = self.variable_for(name);
+                Ok(self.builder.use_var(var))
+            },
+            Expr::Call(_) => Err(unsupported("function calls are not lowered by the native backend yet")),
+            Expr::Binary { op, left, right } => {
+                let lhs = self.lower_expr(left)?;
+                let rhs = self.lower_expr(right)?;
+                Ok(match op {
+                    BinaryOp::Add => self.builder.ins().iadd(lhs, rhs),
+                    BinaryOp::Sub => self.builder.ins().isub(lhs, rhs),
+                    BinaryOp::Mul => self.builder.ins().imul(lhs, rhs),
+                    BinaryOp::Div => self.builder.ins().sdiv(lhs, rhs),
+                    BinaryOp::Eq => self.builder.ins().icmp(
+                        cranelift_codegen::ir::condcodes::IntCC::Equal,
+                        lhs,
+                        rhs,
+                    ),
+                    BinaryOp::Lt => self.builder.ins().icmp(
+                        cranelift_codegen::ir::condcodes::IntCC::SignedLessThan,
+                        lhs,
+                        rhs,
+                    ),
+                    BinaryOp::Gt => self.builder.ins().icmp(
+                        cranelift_codegen::ir::condcodes::IntCC::SignedGreaterThan,
+                        lhs,
+                        rhs,
+                    ),
+                })
+            },
+        }
+    }
+
+    fn variable_for(&mut self, name: &str) -> Variable {
+        if let Some(var) = self.vars.get(name) {
+            return *var;
+        }
+
+        let var = Variable::from_u32(self.next_var as u32);
+        self.next_var += 1;
+        self.builder.declare_var(var, types::I64);
+        self.vars.insert(name.to_string(), var);
+        var
+    }
+
+    fn define_string_data(&mut self, s: &str) -> io::Result<DataId> {
+        let mut bytes = s.as_bytes().to_vec();
+        bytes.push(0);
+
+        let name = format!("str_{}", self.next_string_id);
+        self.next_string_id += 1;
+        let data_id = self
+            .module
+            .declare_data(&name, Linkage::Local, false, false)
+            .map_err(|err| codegen_error(err.to_string()))?;
+
+        let mut description = DataDescription::new();
+        description.define(bytes.into_boxed_slice());
+        self.module
+            .define_data(data_id, &description)
+            .map_err(|err| codegen_error(err.to_string()))?;
+        Ok(data_id)
+    }
+}
+
+fn link_executable() -> io::Result<()> {
+    let runtime_src = concat!(env!("CARGO_MANIFEST_DIR"), "/src/backend/runtime.c");
+    let status = Command::new("cc")
+        .args([OBJECT_PATH, runtime_src, "-o", EXECUTABLE_PATH])
+        .status()?;
+
+    if !status.success() {
+        return Err(unsupported("linking the native executable failed"));
+    }
+    Ok(())
+}
+
+fn codegen_error(message: impl ToString) -> Error {
+    Error::other(message.to_string())
+}
+
+fn unsupported(message: &str) -> Error {
+    Error::new(ErrorKind::Unsupported, message)
+}
@@ -0,0 +1,76 @@
+/// A half-open byte range `[start, end)` into the original source string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+}
+
+/// A compiler error tied to the source range that caused it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl CompileError {
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        CompileError {
+            message: message.into(),
+            span,
+        }
+    }
+}
+
+/// Renders a `CompileError` against `source`, pointing at the offending
+/// range with a `^^^` underline, e.g.:
+///
+/// ```text
+/// error: Unexpected token in expression!
+///   --> line 2, column 9
+///   |
+/// 2 | var x = ;
+///   |         ^
+/// ```
+pub fn render_error(source: &str, error: &CompileError) -> String {
+    let (line, column) = line_col_at(source, error.span.start);
+    let line_text = source.lines().nth(line - 1).unwrap_or("");
+    let underline_len = error.span.end.saturating_sub(error.span.start).max(1);
+
+    let mut rendered = String::new();
+    rendered.push_str(&format!("error: {}\n", error.message));
+    rendered.push_str(&format!("  --> line {}, column {}\n", line, column));
+    rendered.push_str("  |\n");
+    rendered.push_str(&format!("{} | {}\n", line, line_text));
+    rendered.push_str(&format!(
+        "  | {}{}\n",
+        " ".repeat(column - 1),
+        "^".repeat(underline_len)
+    ));
+    rendered
+}
+
+/// Maps a byte offset into `source` to a 1-indexed `(line, column)` pair.
+fn line_col_at(source: &str, offset: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut column = 1;
+
+    for (i, ch) in source.char_indices() {
+        if i >= offset {
+            break;
+        }
+        if ch == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+
+    (line, column)
+}
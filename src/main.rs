@@ -1,214 +1,665 @@
 use std::fs;
-use std::process::Command;
 
-#[derive(Debug)]
+use logos::Logos;
+
+mod analysis;
+mod backend;
+mod diagnostics;
+mod repl;
+
+use diagnostics::{render_error, CompileError, Span};
+
+#[derive(Debug, Clone)]
 enum ValueType {
     Str(String),
     Int(i32),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Logos, Debug, Clone, PartialEq)]
+#[logos(skip r"[ \t\n\r]+")]
+#[logos(skip r"//[^\n]*")]
 enum Token {
+    #[token("var")]
     Var,
+    #[token("mut")]
     Mut,
+    #[token("print")]
+    Print,
+    #[token("if")]
+    If,
+    #[token("else")]
+    Else,
+    #[token("while")]
+    While,
+    #[token("fn")]
+    Fn,
+    #[token("return")]
+    Return,
+    #[regex(r"[a-zA-Z]+", |lex| lex.slice().to_string())]
     Identifier(String),
-    Equals,
+    #[regex(r#""([^"\\]|\\.)*""#, |lex| decode_string(lex.slice()))]
     StringLiteral(String),
+    #[regex(r"[0-9]+", |lex| lex.slice().parse().ok())]
     IntLiteral(i32),
+    #[token("==")]
+    EqualEqual,
+    #[token("=")]
+    Equals,
+    #[token(";")]
     Semicolon,
-    Print,
+    #[token("{")]
     OpenBrace,
+    #[token("}")]
     CloseBrace,
+    #[token("+")]
+    Plus,
+    #[token("-")]
+    Minus,
+    #[token("*")]
+    Star,
+    #[token("/")]
+    Slash,
+    #[token("(")]
+    OpenParen,
+    #[token(")")]
+    CloseParen,
+    #[token(",")]
+    Comma,
+    #[token("<")]
+    LessThan,
+    #[token(">")]
+    GreaterThan,
+}
+
+/// Decodes the backslash escapes (`\"`, `\n`, `\t`, `\\`) in a quoted string
+/// literal's matched slice, stripping the surrounding quotes.
+fn decode_string(slice: &str) -> String {
+    let inner = &slice[1..slice.len() - 1];
+    let mut decoded = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            decoded.push(ch);
+            continue;
+        }
+
+        match chars.next() {
+            Some('"') => decoded.push('"'),
+            Some('n') => decoded.push('\n'),
+            Some('t') => decoded.push('\t'),
+            Some('\\') => decoded.push('\\'),
+            Some(other) => decoded.push(other),
+            None => {},
+        }
+    }
+
+    decoded
+}
+
+#[derive(Debug, Clone)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Lt,
+    Gt,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(ValueType, Span),
+    Ident(String, Span),
+    Call(FunctionCall),
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+}
+
+#[derive(Debug, Clone)]
 struct VariableDeclaration {
     mutable: bool,
     name: String,
-    value: ValueType,
+    span: Span,
+    value: Expr,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct FunctionCall {
     name: String,
-    arguments: Vec<ValueType>,
+    span: Span,
+    arguments: Vec<Expr>,
+}
+
+#[derive(Debug, Clone)]
+struct Assignment {
+    name: String,
+    span: Span,
+    value: Expr,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+struct IfStatement {
+    cond: Expr,
+    then_block: Ast,
+    else_block: Option<Ast>,
+}
+
+#[derive(Debug, Clone)]
+struct WhileStatement {
+    cond: Expr,
+    body: Ast,
+}
+
+#[derive(Debug, Clone)]
+struct FunctionDef {
+    name: String,
+    params: Vec<String>,
+    body: Ast,
+}
+
+#[derive(Debug, Clone)]
 enum Statement {
     VariableDeclaration(VariableDeclaration),
+    Assignment(Assignment),
     FunctionCall(FunctionCall),
+    If(IfStatement),
+    While(WhileStatement),
+    FunctionDef(FunctionDef),
+    Return(Option<Expr>),
 }
 
-type AST = Vec<Statement>;
+type Ast = Vec<Statement>;
 
-fn lexer(input: &str) -> Vec<Token> {
-    let mut tokens = Vec::new();
-    let mut chars = input.chars().peekable();
-    
-    while let Some(&ch) = chars.peek() {
-        match ch {
-            ' ' | '\t' | '\n' | '\r' => { chars.next(); },
-            'a'..='z' | 'A'..='Z' => {
-                let mut name = String::new();
-                while let Some(&ch) = chars.peek() {
-                    match ch {
-                        'a'..='z' | 'A'..='Z' => {
-                            name.push(chars.next().unwrap());
-                        },
-                        _ => break,
-                    }
-                }
+/// Shifts every `Span` reachable from `ast` by `offset`. Used by the REPL
+/// to rebase a freshly parsed line's statements - whose spans start at 0 -
+/// onto the position where that line's text is appended in the
+/// accumulated history source.
+fn offset_spans(ast: &mut Ast, offset: usize) {
+    for statement in ast {
+        offset_statement_spans(statement, offset);
+    }
+}
 
-                if name == "print" {
-                    tokens.push(Token::Print);
-                }
+fn offset_statement_spans(statement: &mut Statement, offset: usize) {
+    match statement {
+        Statement::VariableDeclaration(declaration) => {
+            declaration.span = shift_span(declaration.span, offset);
+            offset_expr_spans(&mut declaration.value, offset);
+        },
+        Statement::Assignment(assignment) => {
+            assignment.span = shift_span(assignment.span, offset);
+            offset_expr_spans(&mut assignment.value, offset);
+        },
+        Statement::FunctionCall(call) => offset_call_spans(call, offset),
+        Statement::If(statement) => {
+            offset_expr_spans(&mut statement.cond, offset);
+            offset_spans(&mut statement.then_block, offset);
+            if let Some(else_block) = &mut statement.else_block {
+                offset_spans(else_block, offset);
+            }
+        },
+        Statement::While(statement) => {
+            offset_expr_spans(&mut statement.cond, offset);
+            offset_spans(&mut statement.body, offset);
+        },
+        Statement::FunctionDef(function) => offset_spans(&mut function.body, offset),
+        Statement::Return(value) => {
+            if let Some(expr) = value {
+                offset_expr_spans(expr, offset);
+            }
+        },
+    }
+}
 
-                if name == "var" {
-                    tokens.push(Token::Var);
-                } else if name == "mut" {
-                    tokens.push(Token::Mut);
-                } else {
-                    tokens.push(Token::Identifier(name));
-                }
+fn offset_expr_spans(expr: &mut Expr, offset: usize) {
+    match expr {
+        Expr::Literal(_, span) => *span = shift_span(*span, offset),
+        Expr::Ident(_, span) => *span = shift_span(*span, offset),
+        Expr::Call(call) => offset_call_spans(call, offset),
+        Expr::Binary { left, right, .. } => {
+            offset_expr_spans(left, offset);
+            offset_expr_spans(right, offset);
+        },
+    }
+}
+
+fn offset_call_spans(call: &mut FunctionCall, offset: usize) {
+    call.span = shift_span(call.span, offset);
+    for argument in &mut call.arguments {
+        offset_expr_spans(argument, offset);
+    }
+}
+
+fn shift_span(span: Span, offset: usize) -> Span {
+    Span::new(span.start + offset, span.end + offset)
+}
+
+/// Tokenizes `input` with a `logos`-derived lexer. Comments (`//` to end of
+/// line) and whitespace are skipped by the `#[logos(skip ...)]` rules above;
+/// any byte sequence none of those rules match surfaces as a `CompileError`
+/// through the diagnostics path instead of being silently dropped.
+fn lexer(input: &str) -> Result<Vec<(Token, Span)>, CompileError> {
+    let mut tokens = Vec::new();
+    let mut lex = Token::lexer(input);
+
+    while let Some(result) = lex.next() {
+        let span = lex.span();
+        match result {
+            Ok(token) => tokens.push((token, Span::new(span.start, span.end))),
+            Err(_) => {
+                return Err(CompileError::new(
+                    format!("Unexpected character(s) `{}`!", &input[span.start..span.end]),
+                    Span::new(span.start, span.end),
+                ));
             },
-            '"' => {
-                chars.next();
-                let mut string = String::new();
-                while let Some(&ch) = chars.peek() {
-                    match ch {
-                        '"' => { chars.next(); break; },
-                        ch => string.push(chars.next().unwrap()),
-                    }
-                }
-                tokens.push(Token::StringLiteral(string));
+        }
+    }
+
+    Ok(tokens)
+}
+
+type Tokens<'a> = std::iter::Peekable<std::slice::Iter<'a, (Token, Span)>>;
+
+/// Returns the `(left, right)` binding power of a binary operator; higher
+/// numbers bind tighter. Comparisons bind loosest, then `+`/`-`, then
+/// `*`/`/`, so `1 + 2 * 3 == 7` parses as `(1 + (2 * 3)) == 7`.
+fn binding_power(op: &BinaryOp) -> (u8, u8) {
+    match op {
+        BinaryOp::Eq | BinaryOp::Lt | BinaryOp::Gt => (0, 1),
+        BinaryOp::Add | BinaryOp::Sub => (1, 2),
+        BinaryOp::Mul | BinaryOp::Div => (3, 4),
+    }
+}
+
+/// Parses a comma-separated, parenthesized call-argument list; the opening
+/// `OpenParen` must already be consumed.
+fn parse_call_arguments(tokens: &mut Tokens, end_span: Span) -> Result<Vec<Expr>, CompileError> {
+    let mut arguments = Vec::new();
+
+    if matches!(tokens.peek(), Some((Token::CloseParen, _))) {
+        tokens.next();
+        return Ok(arguments);
+    }
+
+    loop {
+        arguments.push(parse_expr(tokens, 0, end_span)?);
+        match tokens.next() {
+            Some((Token::Comma, _)) => continue,
+            Some((Token::CloseParen, _)) => break,
+            Some((_, bad_span)) => return Err(CompileError::new("Expected `,` or `)` in argument list!", *bad_span)),
+            None => return Err(CompileError::new("Expected `,` or `)` in argument list!", end_span)),
+        }
+    }
+
+    Ok(arguments)
+}
+
+/// Parses a literal, identifier, call, or parenthesized expression.
+fn parse_primary(tokens: &mut Tokens, end_span: Span) -> Result<Expr, CompileError> {
+    match tokens.next() {
+        Some((Token::StringLiteral(value), span)) => Ok(Expr::Literal(ValueType::Str(value.clone()), *span)),
+        Some((Token::IntLiteral(value), span)) => Ok(Expr::Literal(ValueType::Int(*value), *span)),
+        Some((Token::Identifier(name), span)) => {
+            if matches!(tokens.peek(), Some((Token::OpenParen, _))) {
+                tokens.next(); // consume OpenParen
+                let arguments = parse_call_arguments(tokens, end_span)?;
+                Ok(Expr::Call(FunctionCall { name: name.clone(), span: *span, arguments }))
+            } else {
+                Ok(Expr::Ident(name.clone(), *span))
+            }
+        },
+        Some((Token::OpenParen, _)) => {
+            let expr = parse_expr(tokens, 0, end_span)?;
+            match tokens.next() {
+                Some((Token::CloseParen, _)) => Ok(expr),
+                Some((_, bad_span)) => Err(CompileError::new("Expected closing parenthesis!", *bad_span)),
+                None => Err(CompileError::new("Expected closing parenthesis!", end_span)),
+            }
+        },
+        Some((_, bad_span)) => Err(CompileError::new("Unexpected token in expression!", *bad_span)),
+        None => Err(CompileError::new("Unexpected end of input in expression!", end_span)),
+    }
+}
+
+/// Precedence-climbing (Pratt) expression parser: parses a primary
+/// expression, then repeatedly folds in binary operators whose left
+/// binding power is at least `min_bp`.
+fn parse_expr(tokens: &mut Tokens, min_bp: u8, end_span: Span) -> Result<Expr, CompileError> {
+    let mut lhs = parse_primary(tokens, end_span)?;
+
+    loop {
+        let op = match tokens.peek() {
+            Some((Token::Plus, _)) => BinaryOp::Add,
+            Some((Token::Minus, _)) => BinaryOp::Sub,
+            Some((Token::Star, _)) => BinaryOp::Mul,
+            Some((Token::Slash, _)) => BinaryOp::Div,
+            Some((Token::EqualEqual, _)) => BinaryOp::Eq,
+            Some((Token::LessThan, _)) => BinaryOp::Lt,
+            Some((Token::GreaterThan, _)) => BinaryOp::Gt,
+            _ => break,
+        };
+
+        let (left_bp, right_bp) = binding_power(&op);
+        if left_bp < min_bp {
+            break;
+        }
+
+        tokens.next(); // consume the operator
+        let rhs = parse_expr(tokens, right_bp, end_span)?;
+        lhs = Expr::Binary {
+            op,
+            left: Box::new(lhs),
+            right: Box::new(rhs),
+        };
+    }
+
+    Ok(lhs)
+}
+
+/// Consumes a `{ ... }` block, recursively parsing statements until the
+/// matching `CloseBrace`. The opening `OpenBrace` must already be consumed.
+fn parse_block(tokens: &mut Tokens, end_span: Span) -> Result<Ast, CompileError> {
+    let mut block = Vec::new();
+
+    loop {
+        match tokens.peek() {
+            Some((Token::CloseBrace, _)) => {
+                tokens.next();
+                break;
             },
-            '0'..='9' => {
-                let mut number = String::new();
-                while let Some(&ch) = chars.peek() {
-                    match ch {
-                        '0'..='9' => {
-                            number.push(chars.next().unwrap());
-                        },
-                        _ => break,
-                    }
+            None => return Err(CompileError::new("Expected closing brace!", end_span)),
+            _ => {
+                if let Some(statement) = parse_statement(tokens, end_span)? {
+                    block.push(statement);
                 }
-                tokens.push(Token::IntLiteral(number.parse().unwrap()));
-            },
-            '=' => {
-                chars.next();
-                tokens.push(Token::Equals);
             },
-            ';' => {
-                chars.next();
-                tokens.push(Token::Semicolon);
-            },
-            '{' => {
-                chars.next();
-                tokens.push(Token::OpenBrace);
-            },
-            '}' => {
-                chars.next();
-                tokens.push(Token::CloseBrace);
-            },
-            _ => {
-                chars.next();
-            }
         }
     }
 
-    tokens
+    Ok(block)
 }
 
-fn parser(tokens: &[Token]) -> AST {
-    let mut ast = Vec::new();
-    let mut tokens = tokens.iter().peekable();
+/// Parses the next statement, or `None` once the token stream is exhausted.
+fn parse_statement(tokens: &mut Tokens, end_span: Span) -> Result<Option<Statement>, CompileError> {
+    let (token, span) = match tokens.next() {
+        Some(pair) => pair,
+        None => return Ok(None),
+    };
 
-    while let Some(token) = tokens.next() {
-        match token {
-            Token::Var => {
-                let is_mut = matches!(tokens.peek(), Some(Token::Mut));
-                if is_mut {
-                    tokens.next(); // consume Mut
-                }
-                if let Some(Token::Identifier(name)) = tokens.next() {
-                    tokens.next(); // consume Equals
-                    match tokens.next() {
-                        Some(Token::StringLiteral(value)) => {
-                            ast.push(Statement::VariableDeclaration(VariableDeclaration {
-                                mutable: is_mut,
-                                name: name.clone(),
-                                value: ValueType::Str(value.clone()),
-                            }));
-                        },
-                        Some(Token::IntLiteral(value)) => {
-                            ast.push(Statement::VariableDeclaration(VariableDeclaration {
-                                mutable: is_mut,
-                                name: name.clone(),
-                                value: ValueType::Int(*value),
-                            }));
-                        },
-                        _ => panic!("Unexpected token after equals!"),
-                    }
-                    tokens.next(); // consume Semicolon
-                } else {
-                    panic!("Expected identifier after var/mut!");
+    match token {
+        Token::Var => {
+            let is_mut = matches!(tokens.peek(), Some((Token::Mut, _)));
+            if is_mut {
+                tokens.next(); // consume Mut
+            }
+            if let Some((Token::Identifier(name), ident_span)) = tokens.next() {
+                tokens.next(); // consume Equals
+                let value = parse_expr(tokens, 0, end_span)?;
+                tokens.next(); // consume Semicolon
+                Ok(Some(Statement::VariableDeclaration(VariableDeclaration {
+                    mutable: is_mut,
+                    name: name.clone(),
+                    span: *ident_span,
+                    value,
+                })))
+            } else {
+                Err(CompileError::new("Expected identifier after var/mut!", *span))
+            }
+        },
+        Token::Print => {
+            tokens.next(); // consume '('
+            let argument = parse_expr(tokens, 0, end_span)?;
+            tokens.next(); // consume ')'
+            tokens.next(); // consume Semicolon
+            Ok(Some(Statement::FunctionCall(FunctionCall {
+                name: "print".to_string(),
+                span: *span,
+                arguments: vec![argument],
+            })))
+        },
+        Token::If => {
+            let cond = parse_expr(tokens, 0, end_span)?;
+            tokens.next(); // consume OpenBrace
+            let then_block = parse_block(tokens, end_span)?;
+            let else_block = if matches!(tokens.peek(), Some((Token::Else, _))) {
+                tokens.next(); // consume Else
+                tokens.next(); // consume OpenBrace
+                Some(parse_block(tokens, end_span)?)
+            } else {
+                None
+            };
+            Ok(Some(Statement::If(IfStatement {
+                cond,
+                then_block,
+                else_block,
+            })))
+        },
+        Token::While => {
+            let cond = parse_expr(tokens, 0, end_span)?;
+            tokens.next(); // consume OpenBrace
+            let body = parse_block(tokens, end_span)?;
+            Ok(Some(Statement::While(WhileStatement { cond, body })))
+        },
+        Token::Fn => {
+            let name = match tokens.next() {
+                Some((Token::Identifier(name), _)) => name.clone(),
+                Some((_, bad_span)) => {
+                    return Err(CompileError::new("Expected function name after fn!", *bad_span));
+                },
+                None => return Err(CompileError::new("Expected function name after fn!", end_span)),
+            };
+
+            tokens.next(); // consume OpenParen
+            let mut params = Vec::new();
+            loop {
+                match tokens.next() {
+                    Some((Token::CloseParen, _)) => break,
+                    Some((Token::Comma, _)) => continue,
+                    Some((Token::Identifier(param), _)) => params.push(param.clone()),
+                    Some((_, bad_span)) => {
+                        return Err(CompileError::new("Unexpected token in parameter list!", *bad_span));
+                    },
+                    None => return Err(CompileError::new("Unexpected token in parameter list!", end_span)),
                 }
+            }
+
+            tokens.next(); // consume OpenBrace
+            let body = parse_block(tokens, end_span)?;
+            Ok(Some(Statement::FunctionDef(FunctionDef { name, params, body })))
+        },
+        Token::Return => {
+            if matches!(tokens.peek(), Some((Token::Semicolon, _))) {
+                tokens.next(); // consume Semicolon
+                Ok(Some(Statement::Return(None)))
+            } else {
+                let value = parse_expr(tokens, 0, end_span)?;
+                tokens.next(); // consume Semicolon
+                Ok(Some(Statement::Return(Some(value))))
+            }
+        },
+        Token::Identifier(name) => match tokens.peek() {
+            Some((Token::OpenParen, _)) => {
+                tokens.next(); // consume OpenParen
+                let arguments = parse_call_arguments(tokens, end_span)?;
+                tokens.next(); // consume Semicolon
+                Ok(Some(Statement::FunctionCall(FunctionCall {
+                    name: name.clone(),
+                    span: *span,
+                    arguments,
+                })))
             },
-            Token::Print => {
-                tokens.next(); // consume '('
-                let argument = match tokens.next() {
-                    Some(Token::StringLiteral(value)) => ValueType::Str(value.clone()),
-                    Some(Token::IntLiteral(value)) => ValueType::Int(*value),
-                    _ => panic!("Unexpected token in print arguments!"),
-                };
-                tokens.next(); // consume ')'
-                ast.push(Statement::FunctionCall(FunctionCall {
-                    name: "print".to_string(),
-                    arguments: vec![argument],
-                }));
+            Some((Token::Equals, _)) => {
+                tokens.next(); // consume Equals
+                let value = parse_expr(tokens, 0, end_span)?;
+                tokens.next(); // consume Semicolon
+                Ok(Some(Statement::Assignment(Assignment {
+                    name: name.clone(),
+                    span: *span,
+                    value,
+                })))
             },
-            _ => {}
+            _ => Err(CompileError::new(format!("Unexpected identifier `{}` in statement!", name), *span)),
+        },
+        _ => Err(CompileError::new("Unexpected token in statement!", *span)),
+    }
+}
+
+fn parser(tokens: &[(Token, Span)]) -> Result<Ast, CompileError> {
+    let mut ast = Vec::new();
+    let mut tokens = tokens.iter().peekable();
+    let end_span = tokens
+        .clone()
+        .last()
+        .map(|(_, span)| Span::new(span.end, span.end))
+        .unwrap_or(Span::new(0, 0));
+
+    while tokens.peek().is_some() {
+        if let Some(statement) = parse_statement(&mut tokens, end_span)? {
+            ast.push(statement);
+        }
+    }
+
+    Ok(ast)
+}
+
+/// Renders an `Expr` as a JS expression, parenthesizing binary operators so
+/// the emitted JS preserves the parsed precedence regardless of JS's own
+/// operator precedence.
+fn render_expr(expr: &Expr) -> String {
+    match expr {
+        Expr::Literal(ValueType::Str(s), _) => format!("'{}'", s),
+        Expr::Literal(ValueType::Int(i), _) => i.to_string(),
+        Expr::Ident(name, _) => name.clone(),
+        Expr::Call(call) => format!(
+            "{}({})",
+            call.name,
+            call.arguments.iter().map(render_expr).collect::<Vec<_>>().join(", ")
+        ),
+        Expr::Binary { op, left, right } => {
+            let operator = match op {
+                BinaryOp::Add => "+",
+                BinaryOp::Sub => "-",
+                BinaryOp::Mul => "*",
+                BinaryOp::Div => "/",
+                BinaryOp::Eq => "===",
+                BinaryOp::Lt => "<",
+                BinaryOp::Gt => ">",
+            };
+            format!("({} {} {})", render_expr(left), operator, render_expr(right))
+        },
+    }
+}
+
+/// Finds `{name}` interpolation placeholders in a string literal's
+/// contents, returning each referenced name together with its byte range
+/// within `s` (not the whole source). Only text strictly between a `{` and
+/// its matching `}` is considered a candidate; anything outside braces
+/// (including a string with no braces at all) never is.
+fn find_interpolations(s: &str) -> Vec<(String, std::ops::Range<usize>)> {
+    let mut found = Vec::new();
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((start, ch)) = chars.next() {
+        if ch != '{' {
+            continue;
+        }
+
+        let content_start = start + 1;
+        let mut content_end = content_start;
+        let mut closed = false;
+        while let Some(&(i, ch)) = chars.peek() {
+            if ch == '}' {
+                chars.next();
+                closed = true;
+                break;
+            }
+            content_end = i + ch.len_utf8();
+            chars.next();
+        }
+
+        if closed {
+            let name = &s[content_start..content_end];
+            if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphabetic()) {
+                found.push((name.to_string(), content_start..content_end));
+            }
         }
     }
 
-    ast
+    found
 }
 
-fn generate_js(ast: &AST) -> String {
+fn generate_js(ast: &Ast) -> String {
     let mut js_code = String::new();
 
     for statement in ast {
         match statement {
             Statement::VariableDeclaration(declaration) => {
                 let var_type = if declaration.mutable { "let" } else { "const" };
-                match &declaration.value {
-                    ValueType::Str(s) => {
-                        js_code.push_str(&format!("{} {} = \'{}\'\n", var_type, declaration.name, s));
-                    },
-                    ValueType::Int(i) => {
-                        js_code.push_str(&format!("{} {} = {}\n", var_type, declaration.name, i));
-                    },
-                }
+                js_code.push_str(&format!(
+                    "{} {} = {}\n",
+                    var_type,
+                    declaration.name,
+                    render_expr(&declaration.value)
+                ));
+            },
+            Statement::Assignment(assignment) => {
+                js_code.push_str(&format!("{} = {}\n", assignment.name, render_expr(&assignment.value)));
             },
             Statement::FunctionCall(call) => {
                 if call.name == "print" {
                     for arg in &call.arguments {
                         match arg {
-                            ValueType::Str(s) => {
+                            Expr::Literal(ValueType::Str(s), _) => {
                                 let mut interpolated_str = s.clone();
-                                for segment in s.split(|c| c == '{' || c == '}').collect::<Vec<_>>() {
-                                    if lexer(segment).first() == Some(&Token::Identifier(segment.to_string())) {
-                                        interpolated_str = interpolated_str.replace(&format!("{{{}}}", segment), &format!("${{{}}}", segment));
-                                    }
+                                for (name, _) in find_interpolations(s) {
+                                    interpolated_str = interpolated_str
+                                        .replace(&format!("{{{}}}", name), &format!("${{{}}}", name));
                                 }
                                 js_code.push_str(&format!("console.log(`{}`)\n", interpolated_str));
                             },
-                            _ => {}
+                            other => {
+                                js_code.push_str(&format!("console.log({})\n", render_expr(other)));
+                            },
                         }
                     }
+                } else {
+                    let arguments = call.arguments.iter().map(render_expr).collect::<Vec<_>>().join(", ");
+                    js_code.push_str(&format!("{}({})\n", call.name, arguments));
                 }
-            }
+            },
+            Statement::If(statement) => {
+                js_code.push_str(&format!("if ({}) {{\n", render_expr(&statement.cond)));
+                js_code.push_str(&generate_js(&statement.then_block));
+                js_code.push_str("}\n");
+                if let Some(else_block) = &statement.else_block {
+                    js_code.push_str("else {\n");
+                    js_code.push_str(&generate_js(else_block));
+                    js_code.push_str("}\n");
+                }
+            },
+            Statement::While(statement) => {
+                js_code.push_str(&format!("while ({}) {{\n", render_expr(&statement.cond)));
+                js_code.push_str(&generate_js(&statement.body));
+                js_code.push_str("}\n");
+            },
+            Statement::FunctionDef(function) => {
+                js_code.push_str(&format!(
+                    "function {}({}) {{\n",
+                    function.name,
+                    function.params.join(", ")
+                ));
+                js_code.push_str(&generate_js(&function.body));
+                js_code.push_str("}\n");
+            },
+            Statement::Return(value) => match value {
+                Some(expr) => js_code.push_str(&format!("return {}\n", render_expr(expr))),
+                None => js_code.push_str("return\n"),
+            },
         }
     }
 
@@ -216,20 +667,107 @@ fn generate_js(ast: &AST) -> String {
 }
 
 fn main() {
-    let input_filename = "index.mc";
-    let output_filename = "index.js";
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let mut input_filename = None;
+    let mut target = "js";
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--target" => target = args.next().map(String::as_str).unwrap_or("js"),
+            other => input_filename = Some(other),
+        }
+    }
+    let input_filename = input_filename.unwrap_or("index.mc");
+
+    if input_filename == "repl" || !std::path::Path::new(input_filename).exists() {
+        repl::run_repl();
+        return;
+    }
+
     let code = fs::read_to_string(input_filename)
         .expect("Failed to read the source file.");
 
-    let tokens = lexer(&code);
-    let ast: Vec<Statement> = parser(&tokens);
-    let js_code = generate_js(&ast);
+    let tokens = match lexer(&code) {
+        Ok(tokens) => tokens,
+        Err(error) => {
+            eprint!("{}", render_error(&code, &error));
+            std::process::exit(1);
+        },
+    };
+    let ast: Vec<Statement> = match parser(&tokens) {
+        Ok(ast) => ast,
+        Err(error) => {
+            eprint!("{}", render_error(&code, &error));
+            std::process::exit(1);
+        },
+    };
 
-    fs::write(output_filename, js_code)
-        .expect("Failed to write the output file.");
+    if let Err(error) = analysis::analyze(&ast, &code) {
+        eprint!("{}", render_error(&code, &error));
+        std::process::exit(1);
+    }
+
+    if let Err(err) = backend::select(target).compile_and_run(&ast) {
+        eprintln!("Failed to compile/run program: {}", err);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Command::new("node")
-        .arg(output_filename)
-        .status()
-        .expect("Failed to execute command");
+    #[test]
+    fn decode_string_handles_known_escapes() {
+        assert_eq!(decode_string(r#""a\nb\tc\\d\"e""#), "a\nb\tc\\d\"e");
+    }
+
+    fn parse(source: &str) -> Ast {
+        let tokens = lexer(source).expect("lexer should succeed");
+        parser(&tokens).expect("parser should succeed")
+    }
+
+    #[test]
+    fn parses_var_assignment_as_a_plain_assignment_not_a_redeclaration() {
+        let ast = parse("var mut i = 0; i = i + 1;");
+        assert_eq!(ast.len(), 2);
+        match &ast[1] {
+            Statement::Assignment(assignment) => assert_eq!(assignment.name, "i"),
+            other => panic!("expected an Assignment, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_while_loop_with_a_mutating_condition() {
+        let ast = parse("var mut i = 0; while i < 3 { i = i + 1; }");
+        match &ast[1] {
+            Statement::While(statement) => assert_eq!(statement.body.len(), 1),
+            other => panic!("expected a While statement, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_call_statement_with_its_own_span() {
+        let ast = parse("print(1);");
+        match &ast[0] {
+            Statement::FunctionCall(call) => assert_eq!(call.span, Span::new(0, 5)),
+            other => panic!("expected a FunctionCall, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn find_interpolations_only_matches_braced_identifiers() {
+        let found = find_interpolations("a {name} b {} c {not valid} {ok}");
+        let names: Vec<_> = found.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["name", "ok"]);
+    }
+
+    #[test]
+    fn generate_js_renders_assignment_as_a_plain_statement() {
+        let ast = parse("var mut i = 0; i = i + 1;");
+        let js = generate_js(&ast);
+        assert!(js.contains("i = (i + 1)"));
+        assert!(!js.contains("let i = (i + 1)"));
+    }
 }
@@ -0,0 +1,153 @@
+use std::io::Write;
+use std::process::{Child, Command, Stdio};
+
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+use crate::diagnostics::render_error;
+use crate::{generate_js, lexer, offset_spans, parser, Statement};
+
+const HISTORY_FILE: &str = ".mc_history";
+
+/// Starts an interactive read-eval-print loop for the language.
+///
+/// Every accepted line is appended to a persistent statement history so a
+/// declaration made on one line (`var x = 1;`) is still visible on later
+/// ones (`print(x)`) for re-analysis, but each line's generated JS is only
+/// ever executed once, against a single long-lived `JsSession` - re-running
+/// the whole history on every line would replay earlier side effects (like
+/// `print`) again each time. Type `:ast` to toggle printing the parsed
+/// `Statement`s instead of running the generated JS, and `:quit` to exit.
+pub fn run_repl() {
+    let mut editor = DefaultEditor::new().expect("Failed to start line editor.");
+    let _ = editor.load_history(HISTORY_FILE);
+
+    let mut history: Vec<Statement> = Vec::new();
+    // Every accepted line's text, concatenated in order. `Span`s on a
+    // line's statements are rebased (via `offset_spans`) onto this buffer
+    // as they're accepted, so re-analyzing the whole `history` together
+    // can still point diagnostics at the right source text, even for a
+    // statement entered several lines ago.
+    let mut source = String::new();
+    let mut show_ast = false;
+    let mut session = JsSession::spawn().expect("Failed to start the JS session.");
+
+    println!("mc repl - type :ast to toggle AST dumps, :quit to exit");
+
+    loop {
+        match editor.readline("mc> ") {
+            Ok(line) => {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+
+                let _ = editor.add_history_entry(line);
+
+                match line {
+                    ":quit" | ":exit" => break,
+                    ":ast" => {
+                        show_ast = !show_ast;
+                        println!("ast dump: {}", if show_ast { "on" } else { "off" });
+                        continue;
+                    },
+                    _ => {},
+                }
+
+                let tokens = match lexer(line) {
+                    Ok(tokens) => tokens,
+                    Err(error) => {
+                        eprint!("{}", render_error(line, &error));
+                        continue;
+                    },
+                };
+                let mut statements = match parser(&tokens) {
+                    Ok(statements) => statements,
+                    Err(error) => {
+                        eprint!("{}", render_error(line, &error));
+                        continue;
+                    },
+                };
+
+                let offset = source.len();
+                offset_spans(&mut statements, offset);
+
+                if show_ast {
+                    source.push_str(line);
+                    source.push('\n');
+                    for statement in &statements {
+                        println!("{:#?}", statement);
+                    }
+                    history.extend(statements);
+                    continue;
+                }
+
+                let mut candidate_history = history.clone();
+                candidate_history.extend(statements.clone());
+                let mut candidate_source = source.clone();
+                candidate_source.push_str(line);
+                candidate_source.push('\n');
+
+                if let Err(error) = crate::analysis::analyze(&candidate_history, &candidate_source) {
+                    eprint!("{}", render_error(&candidate_source, &error));
+                    continue;
+                }
+
+                history = candidate_history;
+                source = candidate_source;
+
+                let js_code = generate_js(&statements);
+                if let Err(err) = session.eval(&js_code) {
+                    eprintln!("Failed to run generated JS: {}", err);
+                }
+            },
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => break,
+            Err(err) => {
+                eprintln!("Readline error: {}", err);
+                break;
+            },
+        }
+    }
+
+    let _ = editor.save_history(HISTORY_FILE);
+}
+
+/// A delimiter line unlikely to ever be produced by `generate_js`, used to
+/// mark the end of one line's worth of JS in the pipe to `JsSession`.
+const EVAL_DELIMITER: &str = "__MC_REPL_EOF__";
+
+/// A long-lived `node` child process that evaluates each `eval`-ed chunk of
+/// JS in the same shared scope, via `src/repl_runtime.js`. This is what lets
+/// a `var` declared on one REPL line stay visible on a later one without
+/// re-running the declarations (and any side effects) in between.
+struct JsSession {
+    child: Child,
+}
+
+impl JsSession {
+    fn spawn() -> std::io::Result<Self> {
+        let runtime_src = concat!(env!("CARGO_MANIFEST_DIR"), "/src/repl_runtime.js");
+        let child = Command::new("node").arg(runtime_src).stdin(Stdio::piped()).spawn()?;
+        Ok(JsSession { child })
+    }
+
+    /// Sends `js_code` to the session to be evaluated once, in the shared
+    /// context built up by every prior call.
+    fn eval(&mut self, js_code: &str) -> std::io::Result<()> {
+        let stdin = self.child.stdin.as_mut().expect("stdin was piped at spawn");
+        stdin.write_all(js_code.as_bytes())?;
+        stdin.write_all(b"\n")?;
+        stdin.write_all(EVAL_DELIMITER.as_bytes())?;
+        stdin.write_all(b"\n")?;
+        stdin.flush()
+    }
+}
+
+impl Drop for JsSession {
+    fn drop(&mut self) {
+        // Dropping stdin closes the pipe, which ends the child's readline
+        // loop and lets it exit on its own.
+        self.child.stdin = None;
+        let _ = self.child.wait();
+    }
+}